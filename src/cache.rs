@@ -0,0 +1,164 @@
+use rawr::responses::listing::Submission;
+use rawr::responses::user::UserAboutData;
+use std::collections::HashMap;
+
+/// A pluggable store for fetched about-data and submissions, keyed by a
+/// lowercased name (a username, or `r/<subreddit>/<sort>` for a subreddit
+/// listing's submissions). Populating it lets a long-lived mount replay without touching the
+/// network and keeps browsing sessions under Reddit's rate limits.
+pub trait UserCache {
+  /// Cached about-data for `name`, or `None` on a miss or once the entry is
+  /// older than the configured TTL.
+  fn get_user(&self, name: &str) -> Option<UserAboutData>;
+  /// Record freshly fetched about-data for `name`.
+  fn put_user(&mut self, name: &str, about: &UserAboutData);
+  /// Cached submissions for `name`, or `None` on a miss/expiry.
+  fn get_posts(&self, name: &str) -> Option<Vec<Submission>>;
+  /// Record freshly fetched submissions for `name`.
+  fn put_posts(&mut self, name: &str, posts: &[Submission]);
+}
+
+fn fresh(now: time::Timespec, fetched_at: time::Timespec, ttl: time::Duration) -> bool {
+  now - fetched_at < ttl
+}
+
+/// The default, process-lifetime cache. Entries live only as long as the mount.
+pub struct MemoryCache {
+  ttl: time::Duration,
+  users: HashMap<String, (time::Timespec, UserAboutData)>,
+  posts: HashMap<String, (time::Timespec, Vec<Submission>)>,
+}
+
+impl MemoryCache {
+  pub fn new(ttl: time::Duration) -> MemoryCache {
+    MemoryCache {
+      ttl,
+      users: HashMap::new(),
+      posts: HashMap::new(),
+    }
+  }
+}
+
+impl UserCache for MemoryCache {
+  fn get_user(&self, name: &str) -> Option<UserAboutData> {
+    self
+      .users
+      .get(&name.to_lowercase())
+      .filter(|(fetched_at, _)| fresh(time::get_time(), *fetched_at, self.ttl))
+      .map(|(_, about)| about.clone())
+  }
+  fn put_user(&mut self, name: &str, about: &UserAboutData) {
+    self
+      .users
+      .insert(name.to_lowercase(), (time::get_time(), about.clone()));
+  }
+  fn get_posts(&self, name: &str) -> Option<Vec<Submission>> {
+    self
+      .posts
+      .get(&name.to_lowercase())
+      .filter(|(fetched_at, _)| fresh(time::get_time(), *fetched_at, self.ttl))
+      .map(|(_, posts)| posts.clone())
+  }
+  fn put_posts(&mut self, name: &str, posts: &[Submission]) {
+    self
+      .posts
+      .insert(name.to_lowercase(), (time::get_time(), posts.to_vec()));
+  }
+}
+
+/// A SQLite-backed cache that survives restarts. Serialized about-data and
+/// submissions are stored keyed by the lowercased cache name alongside a fetched-at
+/// timestamp; the schema is created lazily when the database is opened.
+#[cfg(feature = "sqlite")]
+pub struct SqliteCache {
+  ttl: time::Duration,
+  conn: rusqlite::Connection,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteCache {
+  pub fn open<P: AsRef<std::path::Path>>(
+    path: P,
+    ttl: time::Duration,
+  ) -> rusqlite::Result<SqliteCache> {
+    let conn = rusqlite::Connection::open(path)?;
+    conn.execute_batch(
+      "CREATE TABLE IF NOT EXISTS users (
+         name TEXT PRIMARY KEY, fetched_at INTEGER NOT NULL, data TEXT NOT NULL
+       );
+       CREATE TABLE IF NOT EXISTS posts (
+         name TEXT PRIMARY KEY, fetched_at INTEGER NOT NULL, data TEXT NOT NULL
+       );",
+    )?;
+    Ok(SqliteCache { ttl, conn })
+  }
+
+  fn get<T: serde::de::DeserializeOwned>(&self, table: &str, name: &str) -> Option<T> {
+    let row = self
+      .conn
+      .query_row(
+        &format!("SELECT fetched_at, data FROM {} WHERE name = ?1", table),
+        &[&name.to_lowercase()],
+        |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)),
+      )
+      .ok()?;
+    let (fetched_at, data) = row;
+    if !fresh(time::get_time(), time::Timespec::new(fetched_at, 0), self.ttl) {
+      return None;
+    }
+    serde_json::from_str(&data).ok()
+  }
+
+  fn put<T: serde::Serialize>(&mut self, table: &str, name: &str, value: &T) {
+    if let Ok(data) = serde_json::to_string(value) {
+      let _ = self.conn.execute(
+        &format!(
+          "INSERT OR REPLACE INTO {} (name, fetched_at, data) VALUES (?1, ?2, ?3)",
+          table
+        ),
+        &[
+          &name.to_lowercase() as &dyn rusqlite::ToSql,
+          &time::get_time().sec,
+          &data,
+        ],
+      );
+    }
+  }
+}
+
+#[cfg(feature = "sqlite")]
+impl UserCache for SqliteCache {
+  fn get_user(&self, name: &str) -> Option<UserAboutData> {
+    self.get("users", name)
+  }
+  fn put_user(&mut self, name: &str, about: &UserAboutData) {
+    self.put("users", name, about);
+  }
+  fn get_posts(&self, name: &str) -> Option<Vec<Submission>> {
+    self.get("posts", name)
+  }
+  fn put_posts(&mut self, name: &str, posts: &[Submission]) {
+    self.put("posts", name, &posts.to_vec());
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn fresh_just_under_the_ttl() {
+    let fetched_at = time::Timespec::new(1_000, 0);
+    let ttl = time::Duration::seconds(60);
+    let now = time::Timespec::new(1_059, 0);
+    assert!(fresh(now, fetched_at, ttl));
+  }
+
+  #[test]
+  fn fresh_at_and_just_over_the_ttl() {
+    let fetched_at = time::Timespec::new(1_000, 0);
+    let ttl = time::Duration::seconds(60);
+    assert!(!fresh(time::Timespec::new(1_060, 0), fetched_at, ttl));
+    assert!(!fresh(time::Timespec::new(1_061, 0), fetched_at, ttl));
+  }
+}
@@ -3,46 +3,205 @@ extern crate fuse;
 extern crate indexmap;
 extern crate libc;
 extern crate rawr;
+extern crate serde_json;
 extern crate time;
 
+#[cfg(feature = "sqlite")]
+extern crate rusqlite;
+#[cfg(feature = "sqlite")]
+extern crate serde;
+
 use rawr::prelude::*;
-use std::sync::{
-  atomic::{AtomicBool, Ordering},
-  Arc,
-};
-use std::thread;
-use std::time::Duration;
+use std::sync::{Arc, Condvar, Mutex};
 
+mod cache;
 mod user;
 
 const UA: &'static str = "linux:reddit_fs:v0.1.1 (by /u/coolreader18)";
 
+/// Resolve the OAuth script-app credentials, preferring the process environment
+/// and falling back to a JSON config file. Returns the four fields only when all
+/// are present so the mount can opt into authenticated mode.
+fn oauth_credentials(config_path: Option<&str>) -> Option<(String, String, String, String)> {
+  use std::env::var;
+  let from_env = || {
+    Some((
+      var("REDDIT_FS_CLIENT_ID").ok()?,
+      var("REDDIT_FS_CLIENT_SECRET").ok()?,
+      var("REDDIT_FS_USERNAME").ok()?,
+      var("REDDIT_FS_PASSWORD").ok()?,
+    ))
+  };
+  from_env().or_else(|| credentials_from_file(config_path))
+}
+
+/// Load the credentials from a JSON config file holding `client_id`,
+/// `client_secret`, `username`, and `password`. The path comes from `--config`,
+/// then `$REDDIT_FS_CONFIG`, then `$HOME/.config/reddit_fs/config.json`.
+fn credentials_from_file(config_path: Option<&str>) -> Option<(String, String, String, String)> {
+  let path = config_path
+    .map(std::path::PathBuf::from)
+    .or_else(|| std::env::var_os("REDDIT_FS_CONFIG").map(std::path::PathBuf::from))
+    .or_else(|| {
+      std::env::var_os("HOME")
+        .map(|home| std::path::Path::new(&home).join(".config/reddit_fs/config.json"))
+    })?;
+  let text = std::fs::read_to_string(path).ok()?;
+  let json = serde_json::from_str::<serde_json::Value>(&text).ok()?;
+  let field = |key: &str| json[key].as_str().map(str::to_owned);
+  Some((
+    field("client_id")?,
+    field("client_secret")?,
+    field("username")?,
+    field("password")?,
+  ))
+}
+
+/// The mount configuration parsed off the command line.
+struct MountConfig {
+  mountpoint: std::ffi::OsString,
+  fsname: String,
+  subtype: Option<String>,
+  allow_other: bool,
+  auto_unmount: bool,
+  ttl: i64,
+  /// How long fetched about-data and submissions stay fresh in the cache.
+  data_ttl: i64,
+  /// Path to a SQLite cache database; `None` keeps the in-memory cache.
+  cache_db: Option<String>,
+  /// Path to a JSON credentials file, overriding the default lookup.
+  config_path: Option<String>,
+}
+
+/// Parse `<mountpoint> [--allow-other] [--auto-unmount] [--fsname=NAME]
+/// [--subtype=NAME] [--ttl=SECONDS] [--data-ttl=SECONDS] [--cache=PATH]
+/// [--config=PATH]`.
+fn parse_args() -> MountConfig {
+  let mut args = std::env::args_os().skip(1);
+  let mountpoint = args.next().expect("usage: reddit_fs <mountpoint> [options]");
+  let mut config = MountConfig {
+    mountpoint,
+    fsname: "reddit_fs".to_owned(),
+    subtype: None,
+    allow_other: false,
+    auto_unmount: true,
+    ttl: 1,
+    data_ttl: 300,
+    cache_db: None,
+    config_path: None,
+  };
+  for arg in args {
+    let arg = arg.to_string_lossy().into_owned();
+    match arg.splitn(2, '=').collect::<Vec<_>>().as_slice() {
+      ["--allow-other"] => config.allow_other = true,
+      ["--auto-unmount"] => config.auto_unmount = true,
+      ["--no-auto-unmount"] => config.auto_unmount = false,
+      ["--fsname", name] => config.fsname = (*name).to_owned(),
+      ["--subtype", name] => config.subtype = Some((*name).to_owned()),
+      ["--ttl", secs] => config.ttl = secs.parse().expect("--ttl expects an integer"),
+      ["--data-ttl", secs] => {
+        config.data_ttl = secs.parse().expect("--data-ttl expects an integer")
+      }
+      ["--cache", path] => config.cache_db = Some((*path).to_owned()),
+      ["--config", path] => config.config_path = Some((*path).to_owned()),
+      other => panic!("unrecognized argument: {}", other.join("=")),
+    }
+  }
+  config
+}
+
+/// Build the cache backend for the mount: the SQLite-backed store when `--cache`
+/// points at a database (requires the `sqlite` feature), otherwise the default
+/// in-memory cache. Both honour the configured data TTL.
+fn build_cache(config: &MountConfig) -> Box<dyn cache::UserCache> {
+  let data_ttl = time::Duration::seconds(config.data_ttl);
+  match &config.cache_db {
+    #[cfg(feature = "sqlite")]
+    Some(path) => {
+      Box::new(cache::SqliteCache::open(path, data_ttl).expect("couldn't open cache database"))
+    }
+    #[cfg(not(feature = "sqlite"))]
+    Some(_) => panic!("--cache requires building with the `sqlite` feature"),
+    None => Box::new(cache::MemoryCache::new(data_ttl)),
+  }
+}
+
 fn main() {
-  let mountpoint = std::env::args_os().nth(1).unwrap();
-  let options = ["-o", "ro", "-o", "fsname=hello"]
+  let config = parse_args();
+  let credentials = oauth_credentials(config.config_path.as_deref());
+  let authenticated = credentials.is_some();
+
+  // Map the parsed configuration onto the fuse crate's `-o` options. Authenticated
+  // mounts are read-write so the `compose`/`reply` action files can accept writes;
+  // anonymous mounts stay read-only.
+  let mut option_strs = vec![format!("fsname={}", config.fsname)];
+  if let Some(subtype) = &config.subtype {
+    option_strs.push(format!("subtype={}", subtype));
+  }
+  if config.allow_other {
+    option_strs.push("allow_other".to_owned());
+  }
+  if config.auto_unmount {
+    option_strs.push("auto_unmount".to_owned());
+  }
+  if !authenticated {
+    option_strs.push("ro".to_owned());
+  }
+  let options = option_strs
     .iter()
-    .map(|o| o.as_ref())
-    .collect::<Vec<_>>();
-  let client = RedditClient::new(UA, AnonymousAuthenticator::new());
-  let fs = user::UserFS::new(client);
+    .flat_map(|o| vec!["-o".as_ref(), o.as_ref()])
+    .collect::<Vec<&std::ffi::OsStr>>();
 
-  let running = Arc::new(AtomicBool::new(true));
-  let r = running.clone();
+  // When the OAuth credentials are present we log in with rawr's password
+  // authenticator and unlock the authenticated resources; otherwise we fall back
+  // to an anonymous, read-only mount. We stash the credentials on the filesystem
+  // so it can relogin and refresh the bearer token before Reddit's ~1 h token
+  // lifetime expires (see `UserFS::ensure_token`).
+  let fs = match credentials {
+    Some((client_id, client_secret, username, password)) => {
+      let client = RedditClient::new(
+        UA,
+        PasswordAuthenticator::new(&client_id, &client_secret, &username, &password),
+      );
+      user::UserFS::new(client)
+        .authenticated(username.clone())
+        .with_token_refresh(UA, client_id, client_secret, username, password)
+    }
+    None => {
+      let client = RedditClient::new(UA, AnonymousAuthenticator::new());
+      user::UserFS::new(client)
+    }
+  }
+  .with_ttl(time::Duration::seconds(config.ttl))
+  .with_cache(build_cache(&config));
 
+  // SIGINT flips this flag and wakes the main thread off the condvar; no polling.
+  let shutdown = Arc::new((Mutex::new(false), Condvar::new()));
+  let handler = shutdown.clone();
   ctrlc::set_handler(move || {
-    r.store(false, Ordering::SeqCst);
-  }).expect("Error setting Ctrl-C handler");
+    let (lock, cvar) = &*handler;
+    *lock.lock().unwrap() = true;
+    cvar.notify_all();
+  })
+  .expect("Error setting Ctrl-C handler");
 
-  if let Some(str_mountpoint) = mountpoint.to_str() {
+  if let Some(str_mountpoint) = config.mountpoint.to_str() {
     println!("Mounting to {}", str_mountpoint);
   }
-  let _fuse_handle = match unsafe { fuse::spawn_mount(fs, &mountpoint, &options) } {
-    Ok(handle) => handle,
+  // Hold the session for the lifetime of the mount; dropping it unmounts
+  // deterministically, and `auto_unmount` covers an outright kill.
+  let session = match unsafe { fuse::spawn_mount(fs, &config.mountpoint, &options) } {
+    Ok(session) => session,
     Err(err) => return eprintln!("Error mounting: {}", err),
   };
 
-  while running.load(Ordering::SeqCst) {
-    thread::sleep(Duration::from_millis(100));
+  // Block until SIGINT rather than spinning, then tear the mount down by
+  // dropping the session.
+  let (lock, cvar) = &*shutdown;
+  let mut stopped = lock.lock().unwrap();
+  while !*stopped {
+    stopped = cvar.wait(stopped).unwrap();
   }
   println!("Unmounting and exiting");
+  drop(session);
 }
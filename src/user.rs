@@ -1,5 +1,9 @@
+use cache::{MemoryCache, UserCache};
 use e_num::ENum;
-use fuse::{FileAttr, FileType, ReplyAttr, ReplyData, ReplyDirectory, ReplyEntry, Request};
+use fuse::{
+  FileAttr, FileType, ReplyAttr, ReplyData, ReplyDirectory, ReplyEmpty, ReplyEntry, ReplyOpen,
+  ReplyWrite, Request,
+};
 use libc::ENOENT;
 use rawr::errors::APIError;
 use rawr::prelude::*;
@@ -14,12 +18,140 @@ enum Resource {
   #[e_num(constant = 1)]
   Top,
   User(usize),
-  LinkKarma(usize),
-  CommentKarma(usize),
-  Username(usize),
-  Created(usize),
-  Summary(usize),
+  LinkKarma(usize, Format),
+  CommentKarma(usize, Format),
+  Username(usize, Format),
+  Created(usize, Format),
+  Summary(usize, Format),
   UserPosts(usize),
+  Post(usize, usize),
+  PostField(usize, usize, PostField),
+  Inbox,
+  InboxMessage(usize),
+  InboxBody(usize),
+  Saved,
+  SavedPost(usize),
+  SavedField(usize, PostField),
+  Subscribed,
+  Subscription(usize),
+  Subreddits,
+  Subreddit(usize),
+  SubredditSort(usize, Sort),
+  SubredditPost(usize, Sort, usize),
+  SubredditPostField(usize, Sort, usize, PostField),
+  Compose(usize, usize),
+  SavedCompose(usize),
+  SubredditCompose(usize, Sort, usize),
+  InboxReply(usize),
+}
+
+/// Whether writing to `resource` should fire an authenticated API action.
+fn is_writable(resource: Resource) -> bool {
+  match resource {
+    Resource::Compose(..)
+    | Resource::SavedCompose(_)
+    | Resource::SubredditCompose(..)
+    | Resource::InboxReply(_) => true,
+    _ => false,
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ENum)]
+enum Sort {
+  Hot,
+  New,
+  Top,
+  Rising,
+}
+
+impl Sort {
+  fn all() -> [Sort; 4] {
+    use self::Sort::*;
+    [Hot, New, Top, Rising]
+  }
+  fn name(self) -> &'static str {
+    use self::Sort::*;
+    match self {
+      Hot => "hot",
+      New => "new",
+      Top => "top",
+      Rising => "rising",
+    }
+  }
+  fn from_name(name: &str) -> Option<Sort> {
+    Sort::all().iter().cloned().find(|s| s.name() == name)
+  }
+}
+
+/// The renderer chosen for a user resource file from its filename extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ENum)]
+enum Format {
+  Txt,
+  Json,
+  Md,
+}
+
+impl Format {
+  fn from_ext(ext: &str) -> Option<Format> {
+    Some(match ext {
+      "txt" => Format::Txt,
+      "json" => Format::Json,
+      "md" => Format::Md,
+      _ => return None,
+    })
+  }
+}
+
+/// Split a resource name into its base and the `Format` implied by a trailing
+/// extension, defaulting to `Txt` when there's no recognized extension.
+fn split_format(name: &str) -> (&str, Format) {
+  if let Some(dot) = name.rfind('.') {
+    if let Some(fmt) = Format::from_ext(&name[dot + 1..]) {
+      return (&name[..dot], fmt);
+    }
+  }
+  (name, Format::Txt)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ENum)]
+enum PostField {
+  Title,
+  Selftext,
+  Url,
+  Score,
+  Permalink,
+  Created,
+  NumComments,
+}
+
+impl PostField {
+  fn all() -> [PostField; 7] {
+    use self::PostField::*;
+    [
+      Title,
+      Selftext,
+      Url,
+      Score,
+      Permalink,
+      Created,
+      NumComments,
+    ]
+  }
+  fn filename(self) -> &'static str {
+    use self::PostField::*;
+    match self {
+      Title => "title",
+      Selftext => "selftext",
+      Url => "url",
+      Score => "score",
+      Permalink => "permalink",
+      Created => "created",
+      NumComments => "num_comments",
+    }
+  }
+  fn from_filename(name: &str) -> Option<PostField> {
+    PostField::all().iter().cloned().find(|f| f.filename() == name)
+  }
 }
 
 impl Resource {
@@ -33,8 +165,11 @@ impl Resource {
     use self::FileType::*;
     use self::Resource::*;
     match self {
-      Top | User(_) | UserPosts(_) => Directory,
-      LinkKarma(_) | CommentKarma(_) | Username(_) | Created(_) | Summary(_) => RegularFile,
+      Top | User(_) | UserPosts(_) | Post(..) | Inbox | InboxMessage(_) | Saved | SavedPost(_)
+      | Subscribed | Subreddits | Subreddit(_) | SubredditSort(..) | SubredditPost(..) => Directory,
+      LinkKarma(..) | CommentKarma(..) | Username(..) | Created(..) | Summary(..) | PostField(..)
+      | InboxBody(_) | SavedField(..) | Subscription(_) | SubredditPostField(..)
+      | Compose(..) | SavedCompose(_) | SubredditCompose(..) | InboxReply(_) => RegularFile,
     }
   }
 }
@@ -56,6 +191,33 @@ impl User {
       .and_then(|res| Ok(User::new(res.data)))
   }
 
+  /// Render the summary in the requested format: plain text, a JSON object of
+  /// the about-data subset, or a small Markdown card.
+  pub fn summary_fmt(&self, fmt: Format) -> String {
+    match fmt {
+      Format::Txt => self.summary(),
+      Format::Json => format!(
+        "{}\n",
+        serde_json::json!({
+          "name": self.about.name,
+          "link_karma": self.about.link_karma,
+          "comment_karma": self.about.comment_karma,
+          "created": self.about.created,
+        })
+      ),
+      Format::Md => {
+        let age = time::get_time() - self.timespec();
+        format!(
+          "# {name}\n\n- **Link Karma:** {link_karma}\n- **Comment Karma:** {comment_karma}\n- **Redditor for:** {age} years\n",
+          name = self.about.name,
+          link_karma = self.about.link_karma,
+          comment_karma = self.about.comment_karma,
+          age = age.num_days() / 365
+        )
+      }
+    }
+  }
+
   pub fn summary(&self) -> String {
     let age = time::get_time() - time::Timespec::new(self.about.created, 0);
     format!(
@@ -96,10 +258,94 @@ A redditor for {age} years
   }
 }
 
+/// An unread inbox message; rawr doesn't model private messages so we pull the
+/// fields we care about straight out of the JSON listing.
+#[derive(Debug)]
+struct Message {
+  /// The message's fullname (e.g. `t4_…`), used as the parent when replying.
+  name: String,
+  author: String,
+  subject: String,
+  body: String,
+}
+
+impl Message {
+  fn content(&self) -> String {
+    format!(
+      "From: {}\nSubject: {}\n\n{}\n",
+      self.author, self.subject, self.body
+    )
+  }
+}
+
+/// Cached OAuth login state for an authenticated mount. rawr doesn't surface the
+/// bearer token's expiry, so we remember when we last logged in and rebuild the
+/// client with a fresh `PasswordAuthenticator` before the token lifetime runs
+/// out, keeping long-lived mounts authenticated.
+struct TokenState {
+  user_agent: String,
+  client_id: String,
+  client_secret: String,
+  username: String,
+  password: String,
+  refreshed_at: time::Timespec,
+}
+
+/// Reddit access tokens live for an hour; renew a little early so a request
+/// never races an in-flight expiry.
+const TOKEN_REFRESH_AFTER: i64 = 3300;
+
+/// Whether a token last refreshed at `refreshed_at` needs renewing as of `now`.
+fn token_is_stale(now: time::Timespec, refreshed_at: time::Timespec) -> bool {
+  now.sec - refreshed_at.sec >= TOKEN_REFRESH_AFTER
+}
+
+/// Build a `FileAttr` for resources that aren't tied to a particular user, using
+/// the epoch as their timestamp.
+fn plain_attrs(ino: u64, filetype: FileType, size: u64) -> FileAttr {
+  let ts = time::Timespec::new(0, 0);
+  FileAttr {
+    ino,
+    size,
+    blocks: size / 512,
+    atime: ts,
+    mtime: ts,
+    ctime: ts,
+    crtime: ts,
+    kind: filetype,
+    perm: if filetype == FileType::Directory {
+      0o755
+    } else {
+      0o644
+    },
+    nlink: 0,
+    uid: unsafe { libc::getuid() },
+    gid: unsafe { libc::getgid() },
+    rdev: 0,
+    flags: 0,
+  }
+}
+
 pub struct UserFS {
   client: RedditClient,
   users: indexmap::IndexMap<String, User>,
   user_posts: std::collections::HashMap<String, Vec<Submission>>,
+  cache: Box<dyn UserCache>,
+  /// The logged-in user's name, set when the mount is authenticated.
+  me: Option<String>,
+  inbox: Option<Vec<Message>>,
+  saved: Option<Vec<Submission>>,
+  subscribed: Option<Vec<String>>,
+  subreddits: indexmap::IndexMap<String, ()>,
+  listings: std::collections::HashMap<(String, Sort), Vec<Submission>>,
+  /// Per-handle write buffers for the `compose`/`reply` action files, flushed to
+  /// the API on `flush`/`release`.
+  write_buffers: std::collections::HashMap<u64, (Resource, Vec<u8>)>,
+  next_fh: u64,
+  /// How long the kernel may cache attribute/entry lookups.
+  ttl: time::Timespec,
+  /// OAuth login state for refreshing the bearer token; `None` when anonymous.
+  token: Option<TokenState>,
 }
 
 fn fetch_user_posts(client: &RedditClient, username: String) -> Result<Vec<Submission>, APIError> {
@@ -115,23 +361,240 @@ fn fetch_user_posts(client: &RedditClient, username: String) -> Result<Vec<Submi
   )
 }
 
+fn fetch_inbox(client: &RedditClient) -> Result<Vec<Message>, APIError> {
+  let res = client.get_json::<serde_json::Value>("/message/unread?raw_json=1", true)?;
+  Ok(
+    res["data"]["children"]
+      .as_array()
+      .map(Vec::as_slice)
+      .unwrap_or(&[])
+      .iter()
+      .map(|thing| {
+        let data = &thing["data"];
+        Message {
+          name: data["name"].as_str().unwrap_or("").to_owned(),
+          author: data["author"].as_str().unwrap_or("").to_owned(),
+          subject: data["subject"].as_str().unwrap_or("").to_owned(),
+          body: data["body"].as_str().unwrap_or("").to_owned(),
+        }
+      })
+      .collect(),
+  )
+}
+
+fn fetch_saved(client: &RedditClient, username: &str) -> Result<Vec<Submission>, APIError> {
+  let url = format!("/user/{}/saved?raw_json=1&limit=25", username);
+  let result = client.get_json::<Listing>(&url, true)?;
+  Ok(
+    result
+      .data
+      .children
+      .into_iter()
+      .map(|thing| thing.data)
+      .collect(),
+  )
+}
+
+/// Buffer a single `write()` call at `offset`, padding with zeros if it
+/// starts past the current end and discarding anything past it (a later
+/// write never appends after a gap, and `setattr`'s truncate already
+/// shrinks `buf` directly, so this only ever grows or overwrites the tail).
+fn write_at_offset(buf: &mut Vec<u8>, offset: usize, data: &[u8]) {
+  if buf.len() < offset {
+    buf.resize(offset, 0);
+  }
+  buf.truncate(offset);
+  buf.extend_from_slice(data);
+}
+
+/// POST a top-level comment (or message reply) against `thing_id`'s fullname.
+fn submit_comment(client: &RedditClient, thing_id: &str, text: &str) -> Result<(), APIError> {
+  let body = [
+    ("api_type", "json"),
+    ("thing_id", thing_id),
+    ("text", text),
+  ]
+    .iter()
+    .cloned()
+    .collect::<std::collections::HashMap<_, _>>();
+  client.post_success("/api/comment", &body, true)
+}
+
+/// Hit `/r/{name}/about` purely to validate that the subreddit exists;
+/// the response body itself isn't needed for anything.
+fn fetch_subreddit_about(client: &RedditClient, name: &str) -> Result<(), APIError> {
+  let url = format!("/r/{}/about?raw_json=1", name);
+  client.get_json::<serde_json::Value>(&url, false)?;
+  Ok(())
+}
+
+fn fetch_listing(
+  client: &RedditClient,
+  name: &str,
+  sort: Sort,
+) -> Result<Vec<Submission>, APIError> {
+  let url = format!("/r/{}/{}?raw_json=1&limit=25", name, sort.name());
+  let result = client.get_json::<Listing>(&url, false)?;
+  Ok(
+    result
+      .data
+      .children
+      .into_iter()
+      .map(|thing| thing.data)
+      .collect(),
+  )
+}
+
+fn fetch_subscribed(client: &RedditClient) -> Result<Vec<String>, APIError> {
+  let res =
+    client.get_json::<serde_json::Value>("/subreddits/mine/subscriber?raw_json=1&limit=100", true)?;
+  Ok(
+    res["data"]["children"]
+      .as_array()
+      .map(Vec::as_slice)
+      .unwrap_or(&[])
+      .iter()
+      .filter_map(|thing| thing["data"]["display_name"].as_str().map(str::to_owned))
+      .collect(),
+  )
+}
+
 impl UserFS {
   pub fn new(client: RedditClient) -> UserFS {
     UserFS {
       client,
       users: indexmap::IndexMap::default(),
       user_posts: std::collections::HashMap::default(),
+      cache: Box::new(MemoryCache::new(time::Duration::minutes(5))),
+      me: None,
+      inbox: None,
+      saved: None,
+      subscribed: None,
+      subreddits: indexmap::IndexMap::default(),
+      listings: std::collections::HashMap::default(),
+      write_buffers: std::collections::HashMap::default(),
+      next_fh: 1,
+      ttl: time::Timespec::new(1, 0),
+      token: None,
+    }
+  }
+
+  /// Set the attribute/entry cache TTL reported to the kernel.
+  pub fn with_ttl(mut self, ttl: time::Duration) -> UserFS {
+    self.ttl = time::Timespec::new(ttl.num_seconds(), 0);
+    self
+  }
+
+  /// Swap in a different cache backend (e.g. the SQLite-backed one) in place of
+  /// the default in-memory cache.
+  pub fn with_cache(mut self, cache: Box<dyn UserCache>) -> UserFS {
+    self.cache = cache;
+    self
+  }
+
+  /// Mark the filesystem as authenticated for `username`, unlocking the
+  /// `inbox`, `saved`, and `subscribed` top-level directories.
+  pub fn authenticated(mut self, username: String) -> UserFS {
+    self.me = Some(username);
+    self
+  }
+
+  /// Record the OAuth credentials so the mount can transparently refresh its
+  /// bearer token before Reddit expires it; see [`UserFS::ensure_token`].
+  pub fn with_token_refresh(
+    mut self,
+    user_agent: &str,
+    client_id: String,
+    client_secret: String,
+    username: String,
+    password: String,
+  ) -> UserFS {
+    self.token = Some(TokenState {
+      user_agent: user_agent.to_owned(),
+      client_id,
+      client_secret,
+      username,
+      password,
+      refreshed_at: time::get_time(),
+    });
+    self
+  }
+
+  fn is_authenticated(&self) -> bool {
+    self.me.is_some()
+  }
+
+  /// Renew the bearer token when the cached login is close to expiring by
+  /// rebuilding the client with a fresh `PasswordAuthenticator`. Called before
+  /// each fetch; a no-op for anonymous mounts, which carry no credentials.
+  fn ensure_token(&mut self) {
+    let stale = match &self.token {
+      Some(token) => token_is_stale(time::get_time(), token.refreshed_at),
+      None => false,
+    };
+    if !stale {
+      return;
     }
+    let (user_agent, client_id, client_secret, username, password) = {
+      let token = self.token.as_ref().unwrap();
+      (
+        token.user_agent.clone(),
+        token.client_id.clone(),
+        token.client_secret.clone(),
+        token.username.clone(),
+        token.password.clone(),
+      )
+    };
+    let authenticator =
+      PasswordAuthenticator::new(&client_id, &client_secret, &username, &password);
+    self.client = RedditClient::new(&user_agent, authenticator);
+    self.token.as_mut().unwrap().refreshed_at = time::get_time();
+  }
+
+  fn inbox(&mut self) -> Result<&Vec<Message>, APIError> {
+    self.ensure_token();
+    if self.inbox.is_none() {
+      self.inbox = Some(fetch_inbox(&self.client)?);
+    }
+    Ok(self.inbox.as_ref().unwrap())
+  }
+
+  fn saved(&mut self) -> Result<&Vec<Submission>, APIError> {
+    self.ensure_token();
+    if self.saved.is_none() {
+      let me = self.me.clone().expect("saved requires authentication");
+      self.saved = Some(fetch_saved(&self.client, &me)?);
+    }
+    Ok(self.saved.as_ref().unwrap())
+  }
+
+  fn subscribed(&mut self) -> Result<&Vec<String>, APIError> {
+    self.ensure_token();
+    if self.subscribed.is_none() {
+      self.subscribed = Some(fetch_subscribed(&self.client)?);
+    }
+    Ok(self.subscribed.as_ref().unwrap())
   }
 
   fn get_user_by_name(&mut self, name: String) -> Result<(usize, &User), APIError> {
+    self.ensure_token();
     let name = name.to_lowercase();
     let entry = self.users.entry(name.clone());
     let i = entry.index();
     use indexmap::map::Entry;
     let user = match entry {
       Entry::Occupied(o) => o.into_mut(),
-      Entry::Vacant(v) => v.insert(User::fetch(&self.client, name)?),
+      Entry::Vacant(v) => {
+        let about = match self.cache.get_user(&name) {
+          Some(about) => about,
+          None => {
+            let about = User::fetch(&self.client, name.clone())?.about;
+            self.cache.put_user(&name, &about);
+            about
+          }
+        };
+        v.insert(User::new(about))
+      }
     };
     Ok((i, user))
   }
@@ -140,13 +603,103 @@ impl UserFS {
     self.users.get_index(idx).unwrap().1
   }
 
+  /// Register a subreddit by name, returning its stable index. Like
+  /// `get_user_by_name`, this does a network round-trip to confirm the
+  /// subreddit actually exists before admitting it.
+  fn get_subreddit_by_name(&mut self, name: String) -> Result<usize, APIError> {
+    self.ensure_token();
+    let name = name.to_lowercase();
+    use indexmap::map::Entry;
+    match self.subreddits.entry(name.clone()) {
+      Entry::Occupied(o) => Ok(o.index()),
+      Entry::Vacant(v) => {
+        fetch_subreddit_about(&self.client, &name)?;
+        let i = v.index();
+        v.insert(());
+        Ok(i)
+      }
+    }
+  }
+
+  fn get_subreddit(&self, idx: usize) -> &str {
+    self.subreddits.get_index(idx).unwrap().0
+  }
+
+  fn listing(&mut self, idx: usize, sort: Sort) -> Result<&Vec<Submission>, APIError> {
+    self.ensure_token();
+    use std::collections::hash_map::Entry;
+    let name = self.get_subreddit(idx).to_owned();
+    match self.listings.entry((name.clone(), sort)) {
+      Entry::Occupied(o) => Ok(o.into_mut()),
+      Entry::Vacant(v) => {
+        // Route through the shared cache like users and posts, keyed by the
+        // subreddit/sort pair, so listings also survive restarts and honor
+        // `--data-ttl` under the SQLite backend.
+        let key = format!("r/{}/{}", name, sort.name());
+        let posts = match self.cache.get_posts(&key) {
+          Some(posts) => posts,
+          None => {
+            let posts = fetch_listing(&self.client, &name, sort)?;
+            self.cache.put_posts(&key, &posts);
+            posts
+          }
+        };
+        Ok(v.insert(posts))
+      }
+    }
+  }
+
+  fn get_listing(&self, idx: usize, sort: Sort) -> &Vec<Submission> {
+    let name = self.get_subreddit(idx).to_owned();
+    self.listings.get(&(name, sort)).unwrap()
+  }
+
+  fn get_subreddit_post(&self, idx: usize, sort: Sort, post_idx: usize) -> &Submission {
+    &self.get_listing(idx, sort)[post_idx]
+  }
+
+  fn get_posts(&self, user_idx: usize) -> &Vec<Submission> {
+    let username = &self.get_user(user_idx).about.name;
+    self.user_posts.get(username).unwrap()
+  }
+
+  fn get_post(&self, user_idx: usize, post_idx: usize) -> &Submission {
+    &self.get_posts(user_idx)[post_idx]
+  }
+
   fn resource_content(&self, resource: Resource) -> String {
     match resource {
-      Resource::LinkKarma(idx) => format!("{}\n", self.get_user(idx).about.link_karma),
-      Resource::CommentKarma(idx) => format!("{}\n", self.get_user(idx).about.comment_karma),
-      Resource::Created(idx) => format!("{}\n", self.get_user(idx).about.created),
-      Resource::Username(idx) => format!("{}\n", self.get_user(idx).about.name),
-      Resource::Summary(idx) => self.get_user(idx).summary(),
+      Resource::LinkKarma(idx, fmt) => {
+        field_content(fmt, "link_karma", serde_json::json!(self.get_user(idx).about.link_karma))
+      }
+      Resource::CommentKarma(idx, fmt) => field_content(
+        fmt,
+        "comment_karma",
+        serde_json::json!(self.get_user(idx).about.comment_karma),
+      ),
+      Resource::Created(idx, fmt) => {
+        field_content(fmt, "created", serde_json::json!(self.get_user(idx).about.created))
+      }
+      Resource::Username(idx, fmt) => {
+        field_content(fmt, "name", serde_json::json!(self.get_user(idx).about.name))
+      }
+      Resource::Summary(idx, fmt) => self.get_user(idx).summary_fmt(fmt),
+      Resource::PostField(user_idx, post_idx, field) => {
+        post_field(self.get_post(user_idx, post_idx), field)
+      }
+      Resource::InboxBody(idx) => self.inbox.as_ref().unwrap()[idx].content(),
+      Resource::Subscription(idx) => format!("{}\n", self.subscribed.as_ref().unwrap()[idx]),
+      Resource::SavedField(idx, field) => {
+        post_field(&self.saved.as_ref().unwrap()[idx], field)
+      }
+      Resource::SubredditPostField(sr_idx, sort, post_idx, field) => {
+        post_field(self.get_subreddit_post(sr_idx, sort, post_idx), field)
+      }
+      // The action files are write-only; reading them yields nothing.
+      Resource::Compose(..)
+      | Resource::SavedCompose(_)
+      | Resource::SubredditCompose(..)
+      | Resource::InboxReply(_) => String::new(),
       _ => panic!("can't get content of resource"),
     }
   }
@@ -158,24 +711,133 @@ impl UserFS {
     }
   }
 
+  /// Build the `FileAttr` for `ino`, using the owning user's timestamps for a
+  /// user's directory, its files, `_posts` and post fields, and the epoch for
+  /// everything else (including a post's `compose` file).
+  fn attrs(&self, ino: u64) -> FileAttr {
+    let resource = Resource::from_ino(ino);
+    let len = self.resource_len(resource);
+    match resource {
+      Resource::User(val)
+      | Resource::UserPosts(val)
+      | Resource::LinkKarma(val, _)
+      | Resource::CommentKarma(val, _)
+      | Resource::Username(val, _)
+      | Resource::Created(val, _)
+      | Resource::Summary(val, _)
+      | Resource::Post(val, _)
+      | Resource::PostField(val, _, _) => {
+        self.get_user(val).attrs(ino, resource.filetype(), len)
+      }
+      _ => plain_attrs(ino, resource.filetype(), len),
+    }
+  }
+
+  /// Reply to a `lookup` with epoch-timestamped attributes (see `plain_attrs`),
+  /// for the resources that `attrs` doesn't stamp with a user's timestamps.
+  fn reply_plain(&self, resource: Resource, reply: ReplyEntry) {
+    reply.entry(
+      &self.ttl,
+      &plain_attrs(
+        resource.to_ino(),
+        resource.filetype(),
+        self.resource_len(resource),
+      ),
+      0,
+    );
+  }
+
+  /// Flush the buffered bytes for `fh` by firing the matching authenticated API
+  /// call. A closed-but-empty buffer is a no-op.
+  fn commit_write(&mut self, fh: u64) -> Result<(), libc::c_int> {
+    self.ensure_token();
+    let (resource, buf) = match self.write_buffers.remove(&fh) {
+      Some(entry) => entry,
+      None => return Ok(()),
+    };
+    if buf.is_empty() {
+      return Ok(());
+    }
+    let text = String::from_utf8_lossy(&buf).into_owned();
+    let thing_id = match resource {
+      Resource::Compose(user_idx, post_idx) => self.get_post(user_idx, post_idx).name.clone(),
+      Resource::SavedCompose(idx) => self.saved.as_ref().unwrap()[idx].name.clone(),
+      Resource::SubredditCompose(idx, sort, post_idx) => {
+        self.get_subreddit_post(idx, sort, post_idx).name.clone()
+      }
+      // The reply file lives under its message directory, so the target is
+      // concrete; a missing message (inbox never listed, or index gone stale)
+      // fails the write rather than silently posting nothing.
+      Resource::InboxReply(idx) => match self.inbox.as_ref().and_then(|inbox| inbox.get(idx)) {
+        Some(message) => message.name.clone(),
+        None => return Err(libc::EIO),
+      },
+      _ => return Ok(()),
+    };
+    submit_comment(&self.client, &thing_id, &text).map_err(|_| libc::EIO)
+  }
+
   fn user_posts(&mut self, username: String) -> Result<&Vec<Submission>, APIError> {
+    self.ensure_token();
     use std::collections::hash_map::Entry;
 
     match self.user_posts.entry(username.clone()) {
       Entry::Occupied(o) => Ok(o.into_mut()),
-      Entry::Vacant(v) => Ok(v.insert(fetch_user_posts(&self.client, username)?)),
+      Entry::Vacant(v) => {
+        let posts = match self.cache.get_posts(&username) {
+          Some(posts) => posts,
+          None => {
+            let posts = fetch_user_posts(&self.client, username.clone())?;
+            self.cache.put_posts(&username, &posts);
+            posts
+          }
+        };
+        Ok(v.insert(posts))
+      }
     }
   }
 }
 
+/// Render a single scalar field in the requested format.
+fn field_content(fmt: Format, key: &str, value: serde_json::Value) -> String {
+  let plain = match &value {
+    serde_json::Value::String(s) => s.clone(),
+    other => other.to_string(),
+  };
+  match fmt {
+    Format::Txt => format!("{}\n", plain),
+    Format::Json => format!("{}\n", serde_json::json!({ key: value })),
+    Format::Md => format!("**{}:** {}\n", key, plain),
+  }
+}
+
+fn post_field(post: &Submission, field: PostField) -> String {
+  use self::PostField::*;
+  match field {
+    Title => format!("{}\n", post.title),
+    Selftext => format!("{}\n", post.selftext),
+    Url => format!("{}\n", post.url.as_ref().map(String::as_str).unwrap_or("")),
+    Score => format!("{}\n", post.score),
+    Permalink => format!("{}\n", post.permalink),
+    Created => format!("{}\n", post.created as i64),
+    NumComments => format!("{}\n", post.num_comments),
+  }
+}
+
 fn lookup_user_resource(name: &str, i: usize) -> Option<Resource> {
-  Some(match name {
-    "linkkarma" => Resource::LinkKarma(i),
-    "commentkarma" => Resource::CommentKarma(i),
-    "username" => Resource::Username(i),
-    "created" => Resource::Created(i),
-    "summary" => Resource::Summary(i),
-    "_posts" => Resource::UserPosts(i),
+  // `_posts` has no format suffix of its own (see `readdir`/`resource_content`),
+  // so it's matched against the raw name rather than through `split_format` —
+  // otherwise `_posts.json` would silently alias the same directory.
+  if name == "_posts" {
+    return Some(Resource::UserPosts(i));
+  }
+  let (base, fmt) = split_format(name);
+  Some(match base {
+    "linkkarma" => Resource::LinkKarma(i, fmt),
+    "commentkarma" => Resource::CommentKarma(i, fmt),
+    "username" => Resource::Username(i, fmt),
+    "created" => Resource::Created(i, fmt),
+    "summary" => Resource::Summary(i, fmt),
     _ => return None,
   })
 }
@@ -183,11 +845,34 @@ fn lookup_user_resource(name: &str, i: usize) -> Option<Resource> {
 impl fuse::Filesystem for UserFS {
   fn lookup(&mut self, _req: &Request, parent: u64, os_name: &OsStr, reply: ReplyEntry) {
     let name = os_name.to_str().unwrap().to_owned();
+    let ttl = self.ttl;
     match Resource::from_ino(parent) {
       Resource::Top => {
+        if name == "r" {
+          return reply.entry(
+            &self.ttl,
+            &plain_attrs(Resource::Subreddits.to_ino(), FileType::Directory, 0),
+            0,
+          );
+        }
+        if self.is_authenticated() {
+          let special = match name.as_str() {
+            "inbox" => Some(Resource::Inbox),
+            "saved" => Some(Resource::Saved),
+            "subscribed" => Some(Resource::Subscribed),
+            _ => None,
+          };
+          if let Some(resource) = special {
+            return reply.entry(
+              &self.ttl,
+              &plain_attrs(resource.to_ino(), FileType::Directory, 0),
+              0,
+            );
+          }
+        }
         if let Ok((i, user)) = self.get_user_by_name(name) {
           reply.entry(
-            &user.timespec(),
+            &ttl,
             &user.attrs(Resource::User(i).to_ino(), FileType::Directory, 0),
             0,
           );
@@ -202,7 +887,46 @@ impl fuse::Filesystem for UserFS {
         };
         let user = self.get_user(i);
         reply.entry(
-          &user.timespec(),
+          &ttl,
+          &user.attrs(
+            resource.to_ino(),
+            resource.filetype(),
+            self.resource_len(resource),
+          ),
+          0,
+        );
+      }
+      Resource::UserPosts(i) => {
+        let username = self.get_user(i).about.name.clone();
+        let post_idx = match self.user_posts(username) {
+          Ok(posts) => posts.iter().position(|post| post.id == name),
+          Err(_) => return reply.error(ENOENT),
+        };
+        match post_idx {
+          Some(post_idx) => {
+            let resource = Resource::Post(i, post_idx);
+            let user = self.get_user(i);
+            reply.entry(
+              &ttl,
+              &user.attrs(resource.to_ino(), resource.filetype(), 0),
+              0,
+            );
+          }
+          None => reply.error(ENOENT),
+        }
+      }
+      Resource::Post(user_idx, post_idx) => {
+        if name == "compose" {
+          return self.reply_plain(Resource::Compose(user_idx, post_idx), reply);
+        }
+        let field = match PostField::from_filename(name.as_str()) {
+          Some(field) => field,
+          None => return reply.error(ENOENT),
+        };
+        let resource = Resource::PostField(user_idx, post_idx, field);
+        let user = self.get_user(user_idx);
+        reply.entry(
+          &ttl,
           &user.attrs(
             resource.to_ino(),
             resource.filetype(),
@@ -211,49 +935,117 @@ impl fuse::Filesystem for UserFS {
           0,
         );
       }
+      Resource::Inbox => {
+        let idx = match self.inbox() {
+          Ok(inbox) => name
+            .parse::<usize>()
+            .ok()
+            .filter(|&idx| idx < inbox.len()),
+          Err(_) => return reply.error(ENOENT),
+        };
+        match idx {
+          Some(idx) => self.reply_plain(Resource::InboxMessage(idx), reply),
+          None => reply.error(ENOENT),
+        }
+      }
+      Resource::InboxMessage(idx) if name == "message" => {
+        self.reply_plain(Resource::InboxBody(idx), reply)
+      }
+      Resource::InboxMessage(idx) if name == "reply" => {
+        self.reply_plain(Resource::InboxReply(idx), reply)
+      }
+      Resource::InboxMessage(_) => reply.error(ENOENT),
+      Resource::Saved => {
+        let idx = match self.saved() {
+          Ok(saved) => saved.iter().position(|post| post.id == name),
+          Err(_) => return reply.error(ENOENT),
+        };
+        match idx {
+          Some(idx) => self.reply_plain(Resource::SavedPost(idx), reply),
+          None => reply.error(ENOENT),
+        }
+      }
+      Resource::SavedPost(idx) if name == "compose" => {
+        self.reply_plain(Resource::SavedCompose(idx), reply)
+      }
+      Resource::SavedPost(idx) => match PostField::from_filename(name.as_str()) {
+        Some(field) => self.reply_plain(Resource::SavedField(idx, field), reply),
+        None => reply.error(ENOENT),
+      },
+      Resource::Subscribed => {
+        let idx = match self.subscribed() {
+          Ok(subs) => subs.iter().position(|sub| *sub == name),
+          Err(_) => return reply.error(ENOENT),
+        };
+        match idx {
+          Some(idx) => self.reply_plain(Resource::Subscription(idx), reply),
+          None => reply.error(ENOENT),
+        }
+      }
+      Resource::Subreddits => match self.get_subreddit_by_name(name) {
+        Ok(idx) => self.reply_plain(Resource::Subreddit(idx), reply),
+        Err(_) => reply.error(ENOENT),
+      },
+      Resource::Subreddit(idx) => match Sort::from_name(name.as_str()) {
+        Some(sort) => self.reply_plain(Resource::SubredditSort(idx, sort), reply),
+        None => reply.error(ENOENT),
+      },
+      Resource::SubredditSort(idx, sort) => {
+        let post_idx = match self.listing(idx, sort) {
+          Ok(posts) => posts.iter().position(|post| post.id == name),
+          Err(_) => return reply.error(ENOENT),
+        };
+        match post_idx {
+          Some(post_idx) => self.reply_plain(Resource::SubredditPost(idx, sort, post_idx), reply),
+          None => reply.error(ENOENT),
+        }
+      }
+      Resource::SubredditPost(idx, sort, post_idx) if name == "compose" => {
+        self.reply_plain(Resource::SubredditCompose(idx, sort, post_idx), reply)
+      }
+      Resource::SubredditPost(idx, sort, post_idx) => {
+        match PostField::from_filename(name.as_str()) {
+          Some(field) => {
+            self.reply_plain(Resource::SubredditPostField(idx, sort, post_idx, field), reply)
+          }
+          None => reply.error(ENOENT),
+        }
+      }
       _ => {}
     }
   }
 
   fn getattr(&mut self, _req: &Request, ino: u64, reply: ReplyAttr) {
-    let resource = Resource::from_ino(ino);
-    match resource {
-      Resource::Top => {
-        let ts = time::Timespec::new(0, 0);
-        reply.attr(
-          &ts,
-          &FileAttr {
-            ino,
-            size: 0,
-            blocks: 0,
-            atime: ts,
-            mtime: ts,
-            ctime: ts,
-            crtime: ts,
-            kind: FileType::Directory,
-            perm: 0o755,
-            nlink: 0,
-            uid: unsafe { libc::getuid() },
-            gid: unsafe { libc::getgid() },
-            rdev: 0,
-            flags: 0,
-          },
-        );
-      }
-      Resource::User(val)
-      | Resource::UserPosts(val)
-      | Resource::LinkKarma(val)
-      | Resource::CommentKarma(val)
-      | Resource::Username(val)
-      | Resource::Created(val)
-      | Resource::Summary(val) => {
-        let user = self.get_user(val);
-        reply.attr(
-          &user.timespec(),
-          &user.attrs(ino, resource.filetype(), self.resource_len(resource)),
-        );
+    reply.attr(&self.ttl, &self.attrs(ino));
+  }
+
+  /// Accept (and largely ignore) attribute changes. The only one that matters is
+  /// the `O_TRUNC` a shell redirect (`echo text > compose`) turns into a
+  /// `SETATTR(size=0)`; we shrink the matching write buffer and report the
+  /// resource's attributes so the open succeeds instead of failing `ENOSYS`.
+  fn setattr(
+    &mut self,
+    _req: &Request,
+    ino: u64,
+    _mode: Option<u32>,
+    _uid: Option<u32>,
+    _gid: Option<u32>,
+    size: Option<u64>,
+    _atime: Option<time::Timespec>,
+    _mtime: Option<time::Timespec>,
+    fh: Option<u64>,
+    _crtime: Option<time::Timespec>,
+    _chgtime: Option<time::Timespec>,
+    _bkuptime: Option<time::Timespec>,
+    _flags: Option<u32>,
+    reply: ReplyAttr,
+  ) {
+    if let (Some(size), Some(fh)) = (size, fh) {
+      if let Some((_, buf)) = self.write_buffers.get_mut(&fh) {
+        buf.resize(size as usize, 0);
       }
     }
+    reply.attr(&self.ttl, &self.attrs(ino));
   }
 
   fn read(
@@ -269,6 +1061,62 @@ impl fuse::Filesystem for UserFS {
     reply.data(data.as_bytes());
   }
 
+  fn open(&mut self, _req: &Request, ino: u64, _flags: u32, reply: ReplyOpen) {
+    let resource = Resource::from_ino(ino);
+    if is_writable(resource) {
+      let fh = self.next_fh;
+      self.next_fh += 1;
+      self.write_buffers.insert(fh, (resource, Vec::new()));
+      reply.opened(fh, 0);
+    } else {
+      reply.opened(0, 0);
+    }
+  }
+
+  fn write(
+    &mut self,
+    _req: &Request,
+    _ino: u64,
+    fh: u64,
+    offset: i64,
+    data: &[u8],
+    _flags: u32,
+    reply: ReplyWrite,
+  ) {
+    match self.write_buffers.get_mut(&fh) {
+      Some((_, buf)) => {
+        write_at_offset(buf, offset as usize, data);
+        reply.written(data.len() as u32);
+      }
+      None => reply.error(libc::EIO),
+    }
+  }
+
+  fn flush(&mut self, _req: &Request, _ino: u64, fh: u64, _lock_owner: u64, reply: ReplyEmpty) {
+    match self.commit_write(fh) {
+      Ok(()) => reply.ok(),
+      Err(errno) => reply.error(errno),
+    }
+  }
+
+  fn release(
+    &mut self,
+    _req: &Request,
+    _ino: u64,
+    fh: u64,
+    _flags: u32,
+    _lock_owner: u64,
+    _flush: bool,
+    reply: ReplyEmpty,
+  ) {
+    // `flush` usually commits first; commit anything still buffered so a handle
+    // that was never flushed still fires its action (and its buffer is freed).
+    match self.commit_write(fh) {
+      Ok(()) => reply.ok(),
+      Err(errno) => reply.error(errno),
+    }
+  }
+
   fn readdir(
     &mut self,
     _req: &Request,
@@ -277,14 +1125,27 @@ impl fuse::Filesystem for UserFS {
     offset: i64,
     mut reply: ReplyDirectory,
   ) {
-    let mut out: Vec<(u64, FileType, &str)> = vec![
-      (1, FileType::Directory, "."),
-      (1, FileType::Directory, ".."),
+    use std::borrow::Cow;
+    let mut out: Vec<(u64, FileType, Cow<str>)> = vec![
+      (1, FileType::Directory, ".".into()),
+      (1, FileType::Directory, "..".into()),
     ];
     match Resource::from_ino(ino) {
-      Resource::Top => for (i, user) in self.users.keys().enumerate() {
-        out.push((Resource::User(i).to_ino(), FileType::Directory, user));
-      },
+      Resource::Top => {
+        for (i, user) in self.users.keys().enumerate() {
+          out.push((Resource::User(i).to_ino(), FileType::Directory, user.into()));
+        }
+        out.push((Resource::Subreddits.to_ino(), FileType::Directory, "r".into()));
+        if self.is_authenticated() {
+          for (resource, name) in &[
+            (Resource::Inbox, "inbox"),
+            (Resource::Saved, "saved"),
+            (Resource::Subscribed, "subscribed"),
+          ] {
+            out.push((resource.to_ino(), FileType::Directory, (*name).into()));
+          }
+        }
+      }
       Resource::User(idx) => out.extend(
         [
           "linkkarma",
@@ -297,19 +1158,238 @@ impl fuse::Filesystem for UserFS {
           .iter()
           .map(move |filename| {
             let resource = lookup_user_resource(filename, idx).unwrap();
-            (resource.to_ino() as u64, resource.filetype(), *filename)
+            (resource.to_ino(), resource.filetype(), (*filename).into())
           }),
       ),
       Resource::UserPosts(idx) => {
         let username = self.get_user(idx).about.name.clone();
-        let posts = self.user_posts(username).expect("Couldn't get posts");
-        for Submission { .. } in posts.iter() {}
+        let posts = match self.user_posts(username) {
+          Ok(posts) => posts,
+          Err(_) => return reply.error(libc::EIO),
+        };
+        for (i, post) in posts.iter().enumerate() {
+          out.push((
+            Resource::Post(idx, i).to_ino(),
+            FileType::Directory,
+            post.id.as_str().into(),
+          ));
+        }
+      }
+      Resource::Post(user_idx, post_idx) => {
+        out.extend(PostField::all().iter().map(move |field| {
+          let resource = Resource::PostField(user_idx, post_idx, *field);
+          (resource.to_ino(), resource.filetype(), field.filename().into())
+        }));
+        out.push((
+          Resource::Compose(user_idx, post_idx).to_ino(),
+          FileType::RegularFile,
+          "compose".into(),
+        ));
+      }
+      Resource::Inbox => {
+        let messages = match self.inbox() {
+          Ok(messages) => messages,
+          Err(_) => return reply.error(libc::EIO),
+        };
+        for i in 0..messages.len() {
+          out.push((
+            Resource::InboxMessage(i).to_ino(),
+            FileType::Directory,
+            i.to_string().into(),
+          ));
+        }
+      }
+      Resource::InboxMessage(idx) => {
+        out.push((
+          Resource::InboxBody(idx).to_ino(),
+          FileType::RegularFile,
+          "message".into(),
+        ));
+        out.push((
+          Resource::InboxReply(idx).to_ino(),
+          FileType::RegularFile,
+          "reply".into(),
+        ));
+      }
+      Resource::Saved => {
+        let saved = match self.saved() {
+          Ok(saved) => saved,
+          Err(_) => return reply.error(libc::EIO),
+        };
+        for (i, post) in saved.iter().enumerate() {
+          out.push((
+            Resource::SavedPost(i).to_ino(),
+            FileType::Directory,
+            post.id.clone().into(),
+          ));
+        }
+      }
+      Resource::SavedPost(idx) => {
+        out.extend(PostField::all().iter().map(move |field| {
+          let resource = Resource::SavedField(idx, *field);
+          (resource.to_ino(), resource.filetype(), field.filename().into())
+        }));
+        out.push((
+          Resource::SavedCompose(idx).to_ino(),
+          FileType::RegularFile,
+          "compose".into(),
+        ));
+      }
+      Resource::Subscribed => {
+        let subs = match self.subscribed() {
+          Ok(subs) => subs,
+          Err(_) => return reply.error(libc::EIO),
+        };
+        for (i, sub) in subs.iter().enumerate() {
+          out.push((
+            Resource::Subscription(i).to_ino(),
+            FileType::RegularFile,
+            sub.clone().into(),
+          ));
+        }
+      }
+      Resource::Subreddits => {
+        for (i, name) in self.subreddits.keys().enumerate() {
+          out.push((
+            Resource::Subreddit(i).to_ino(),
+            FileType::Directory,
+            name.into(),
+          ));
+        }
+      }
+      Resource::Subreddit(idx) => out.extend(Sort::all().iter().map(move |sort| {
+        let resource = Resource::SubredditSort(idx, *sort);
+        (resource.to_ino(), resource.filetype(), sort.name().into())
+      })),
+      Resource::SubredditSort(idx, sort) => {
+        let posts = match self.listing(idx, sort) {
+          Ok(posts) => posts,
+          Err(_) => return reply.error(libc::EIO),
+        };
+        for (i, post) in posts.iter().enumerate() {
+          out.push((
+            Resource::SubredditPost(idx, sort, i).to_ino(),
+            FileType::Directory,
+            post.id.clone().into(),
+          ));
+        }
+      }
+      Resource::SubredditPost(idx, sort, post_idx) => {
+        out.extend(PostField::all().iter().map(move |field| {
+          let resource = Resource::SubredditPostField(idx, sort, post_idx, *field);
+          (resource.to_ino(), resource.filetype(), field.filename().into())
+        }));
+        out.push((
+          Resource::SubredditCompose(idx, sort, post_idx).to_ino(),
+          FileType::RegularFile,
+          "compose".into(),
+        ));
       }
       _ => return reply.error(libc::ENOTDIR),
     };
     for (i, (ino, file_type, filename)) in out.iter().enumerate().skip(offset as usize) {
-      reply.add(*ino, i as i64 + 1, *file_type, filename);
+      reply.add(*ino, i as i64 + 1, *file_type, &**filename);
     }
     reply.ok();
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn post_field_from_filename_round_trips_all_fields() {
+    for field in PostField::all().iter() {
+      assert_eq!(PostField::from_filename(field.filename()), Some(*field));
+    }
+  }
+
+  #[test]
+  fn post_field_from_filename_rejects_unknown_names() {
+    assert_eq!(PostField::from_filename("nonexistent"), None);
+  }
+
+  #[test]
+  fn sort_from_name_round_trips_all_sorts() {
+    for sort in Sort::all().iter() {
+      assert_eq!(Sort::from_name(sort.name()), Some(*sort));
+    }
+  }
+
+  #[test]
+  fn sort_from_name_rejects_unknown_names() {
+    assert_eq!(Sort::from_name("controversial"), None);
+  }
+
+  #[test]
+  fn format_from_ext_recognizes_known_extensions() {
+    assert_eq!(Format::from_ext("txt"), Some(Format::Txt));
+    assert_eq!(Format::from_ext("json"), Some(Format::Json));
+    assert_eq!(Format::from_ext("md"), Some(Format::Md));
+  }
+
+  #[test]
+  fn format_from_ext_rejects_unknown_extensions() {
+    assert_eq!(Format::from_ext("yaml"), None);
+  }
+
+  #[test]
+  fn split_format_strips_recognized_extension() {
+    let (base, fmt) = split_format("summary.json");
+    assert_eq!(base, "summary");
+    assert_eq!(fmt, Format::Json);
+  }
+
+  #[test]
+  fn split_format_defaults_to_txt_without_a_recognized_extension() {
+    let (base, fmt) = split_format("summary");
+    assert_eq!(base, "summary");
+    assert_eq!(fmt, Format::Txt);
+
+    let (base, fmt) = split_format("summary.exe");
+    assert_eq!(base, "summary.exe");
+    assert_eq!(fmt, Format::Txt);
+  }
+
+  #[test]
+  fn write_at_offset_appends_at_the_end() {
+    let mut buf = b"hello".to_vec();
+    write_at_offset(&mut buf, 5, b" world");
+    assert_eq!(buf, b"hello world");
+  }
+
+  #[test]
+  fn write_at_offset_overwrites_the_tail() {
+    let mut buf = b"hello world".to_vec();
+    write_at_offset(&mut buf, 6, b"there");
+    assert_eq!(buf, b"hello there");
+  }
+
+  #[test]
+  fn write_at_offset_pads_with_zeros_past_the_end() {
+    let mut buf = b"hi".to_vec();
+    write_at_offset(&mut buf, 4, b"!!");
+    assert_eq!(buf, b"hi\0\0!!");
+  }
+
+  #[test]
+  fn token_is_stale_below_the_refresh_threshold() {
+    let refreshed_at = time::Timespec::new(1_000, 0);
+    let now = time::Timespec::new(1_000 + TOKEN_REFRESH_AFTER - 1, 0);
+    assert!(!token_is_stale(now, refreshed_at));
+  }
+
+  #[test]
+  fn token_is_stale_at_and_past_the_refresh_threshold() {
+    let refreshed_at = time::Timespec::new(1_000, 0);
+    assert!(token_is_stale(
+      time::Timespec::new(1_000 + TOKEN_REFRESH_AFTER, 0),
+      refreshed_at
+    ));
+    assert!(token_is_stale(
+      time::Timespec::new(1_000 + TOKEN_REFRESH_AFTER + 60, 0),
+      refreshed_at
+    ));
+  }
+}